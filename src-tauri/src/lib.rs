@@ -1,15 +1,65 @@
 use base64::prelude::*;
+use lofty::file::FileType;
+use lofty::picture::{MimeType, Picture, PictureType};
 use lofty::prelude::*;
 use lofty::probe::Probe;
-use std::path::Path;
+use lofty::tag::ItemKey;
+use rayon::prelude::*;
+use sha2::{Digest, Sha256};
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use tauri::Manager;
+use walkdir::WalkDir;
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, serde::Deserialize)]
 struct AudioMetadata {
     title: Option<String>,
     artist: Option<String>,
     album: Option<String>,
     duration: Option<u64>,
     cover: Option<String>,
+    #[serde(default)]
+    overall_bitrate: Option<u32>,
+    #[serde(default)]
+    audio_bitrate: Option<u32>,
+    #[serde(default)]
+    sample_rate: Option<u32>,
+    #[serde(default)]
+    channels: Option<u8>,
+    #[serde(default)]
+    bit_depth: Option<u8>,
+    #[serde(default)]
+    codec: Option<String>,
+}
+
+// Human-readable label for a probed file type, e.g. "MPEG" (MP3 files) or "FLAC".
+fn codec_name(file_type: FileType) -> String {
+    format!("{file_type:?}").to_uppercase()
+}
+
+#[derive(serde::Serialize)]
+struct LyricLine {
+    offset_ms: i64,
+    text: String,
+}
+
+#[derive(serde::Serialize)]
+struct Lyrics {
+    lines: Vec<LyricLine>,
+    synced: bool,
+}
+
+#[derive(serde::Serialize)]
+struct ScannedTrack {
+    id: String,
+    path: String,
+    title: Option<String>,
+    artist: Option<String>,
+    album: Option<String>,
+    duration: Option<u64>,
+    has_cover: bool,
 }
 
 // Validate that the path is safe and allowed
@@ -36,45 +86,347 @@ fn is_valid_audio_path(path: &Path) -> bool {
     }
 }
 
-#[tauri::command]
-fn get_metadata(path: String) -> Option<AudioMetadata> {
-    let path = Path::new(&path);
+// Validate that the path is safe, allowed, and writable
+fn is_valid_writable_audio_path(path: &Path) -> bool {
+    if !is_valid_audio_path(path) {
+        return false;
+    }
 
+    match File::open(path).and_then(|f| f.metadata()) {
+        Ok(metadata) => !metadata.permissions().readonly(),
+        Err(_) => false,
+    }
+}
+
+// Resolve (and create) the app-cache directory that holds cached cover thumbnails.
+fn thumbnails_dir(app: &tauri::AppHandle) -> Option<PathBuf> {
+    let dir = app.path().app_cache_dir().ok()?.join("thumbnails");
+    std::fs::create_dir_all(&dir).ok()?;
+    Some(dir)
+}
+
+fn extension_for_mime(mime: Option<&MimeType>) -> &'static str {
+    match mime {
+        Some(MimeType::Png) => "png",
+        Some(MimeType::Jpeg) => "jpg",
+        Some(MimeType::Gif) => "gif",
+        Some(MimeType::Bmp) => "bmp",
+        Some(MimeType::Tiff) => "tiff",
+        _ => "bin",
+    }
+}
+
+// Write `picture` to the thumbnail cache (if not already there) and return its
+// path, keyed by the SHA-256 of its bytes so identical covers are only stored once.
+fn cache_cover(app: &tauri::AppHandle, picture: &Picture) -> Option<String> {
+    let cache_dir = thumbnails_dir(app)?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(picture.data());
+    let hash = format!("{:x}", hasher.finalize());
+
+    let file_path = cache_dir.join(format!("{}.{}", hash, extension_for_mime(picture.mime_type())));
+    if !file_path.exists() {
+        std::fs::write(&file_path, picture.data()).ok()?;
+    }
+
+    Some(file_path.to_string_lossy().into_owned())
+}
+
+fn encode_cover(app: &tauri::AppHandle, picture: &Picture, inline_cover: bool) -> Option<String> {
+    if inline_cover {
+        let b64 = BASE64_STANDARD.encode(picture.data());
+        let mime = picture
+            .mime_type()
+            .map(|m| m.as_str())
+            .unwrap_or("image/jpeg");
+        Some(format!("data:{};base64,{}", mime, b64))
+    } else {
+        cache_cover(app, picture)
+    }
+}
+
+fn read_metadata(app: &tauri::AppHandle, path: &Path, inline_cover: bool) -> Option<AudioMetadata> {
     // Validate path before processing
     if !is_valid_audio_path(path) {
         return None;
     }
 
-    let tagged_file_res = Probe::open(path).ok().and_then(|probe| probe.read().ok());
+    let tagged_file = Probe::open(path).ok()?.read().ok()?;
+
+    let file_type = tagged_file.file_type();
+    let tag = tagged_file
+        .primary_tag()
+        .or_else(|| tagged_file.first_tag());
+    let properties = tagged_file.properties();
+
+    let cover = tag
+        .and_then(|t| t.pictures().first())
+        .and_then(|picture| encode_cover(app, picture, inline_cover));
+
+    Some(AudioMetadata {
+        title: tag.and_then(|t| t.title().map(|s| s.into_owned())),
+        artist: tag.and_then(|t| t.artist().map(|s| s.into_owned())),
+        album: tag.and_then(|t| t.album().map(|s| s.into_owned())),
+        duration: Some(properties.duration().as_secs()),
+        cover,
+        overall_bitrate: properties.overall_bitrate(),
+        audio_bitrate: properties.audio_bitrate(),
+        sample_rate: properties.sample_rate(),
+        channels: properties.channels(),
+        bit_depth: properties.bit_depth(),
+        codec: Some(codec_name(file_type)),
+    })
+}
+
+#[tauri::command]
+fn get_metadata(app: tauri::AppHandle, path: String, inline_cover: bool) -> Option<AudioMetadata> {
+    read_metadata(&app, Path::new(&path), inline_cover)
+}
+
+#[tauri::command]
+fn get_metadata_batch(
+    app: tauri::AppHandle,
+    paths: Vec<String>,
+    inline_cover: bool,
+) -> Vec<Option<AudioMetadata>> {
+    paths
+        .par_iter()
+        .map(|path| read_metadata(&app, Path::new(path), inline_cover))
+        .collect()
+}
+
+// Derive a stable id for a track from its path, so the frontend can key on it
+// across scans without re-hashing file contents.
+fn stable_track_id(path: &Path) -> String {
+    let mut hasher = DefaultHasher::new();
+    path.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+fn scan_track(path: &Path) -> Option<ScannedTrack> {
+    let tagged_file = Probe::open(path).ok()?.read().ok()?;
+    let tag = tagged_file
+        .primary_tag()
+        .or_else(|| tagged_file.first_tag());
+    let properties = tagged_file.properties();
+
+    Some(ScannedTrack {
+        id: stable_track_id(path),
+        path: path.to_string_lossy().into_owned(),
+        title: tag.and_then(|t| t.title().map(|s| s.into_owned())),
+        artist: tag.and_then(|t| t.artist().map(|s| s.into_owned())),
+        album: tag.and_then(|t| t.album().map(|s| s.into_owned())),
+        duration: Some(properties.duration().as_secs()),
+        has_cover: tag.map(|t| !t.pictures().is_empty()).unwrap_or(false),
+    })
+}
+
+#[tauri::command]
+fn scan_library(dir: String) -> Vec<ScannedTrack> {
+    let dir = PathBuf::from(dir);
+    if !dir.is_dir() {
+        return Vec::new();
+    }
+
+    let paths: Vec<PathBuf> = WalkDir::new(&dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.into_path())
+        .filter(|path| is_valid_audio_path(path))
+        .collect();
+
+    paths
+        .par_iter()
+        .filter_map(|path| scan_track(path))
+        .collect()
+}
+
+// Decode a `data:<mime>;base64,<payload>` string into raw bytes and a MIME type
+fn decode_cover_data_url(data_url: &str) -> Result<(Vec<u8>, MimeType), String> {
+    let rest = data_url
+        .strip_prefix("data:")
+        .ok_or_else(|| "cover is not a data URL".to_string())?;
+    let (mime, b64) = rest
+        .split_once(";base64,")
+        .ok_or_else(|| "cover data URL is missing a base64 payload".to_string())?;
+
+    let data = BASE64_STANDARD
+        .decode(b64)
+        .map_err(|e| format!("failed to decode cover base64: {e}"))?;
+
+    Ok((data, MimeType::from(mime)))
+}
+
+#[tauri::command]
+fn set_metadata(path: String, metadata: AudioMetadata) -> Result<(), String> {
+    let path = Path::new(&path);
+
+    if !is_valid_writable_audio_path(path) {
+        return Err("path is not a writable audio file".to_string());
+    }
+
+    let mut tagged_file = Probe::open(path)
+        .map_err(|e| format!("failed to open {}: {e}", path.display()))?
+        .read()
+        .map_err(|e| format!("failed to read tags from {}: {e}", path.display()))?;
+
+    let tag = match tagged_file.primary_tag_mut() {
+        Some(tag) => tag,
+        None => {
+            let tag_type = tagged_file.primary_tag_type();
+            tagged_file.insert_tag(lofty::tag::Tag::new(tag_type));
+            tagged_file.primary_tag_mut().expect("tag was just inserted")
+        }
+    };
+
+    match metadata.title {
+        Some(title) => tag.set_title(title),
+        None => tag.remove_title(),
+    }
+    match metadata.artist {
+        Some(artist) => tag.set_artist(artist),
+        None => tag.remove_artist(),
+    }
+    match metadata.album {
+        Some(album) => tag.set_album(album),
+        None => tag.remove_album(),
+    }
+
+    if let Some(cover) = metadata.cover {
+        let (data, mime_type) = decode_cover_data_url(&cover)?;
+        tag.remove_picture_type(PictureType::CoverFront);
+        tag.push_picture(Picture::new_unchecked(
+            PictureType::CoverFront,
+            Some(mime_type),
+            None,
+            data,
+        ));
+    }
+
+    tagged_file
+        .save_to_path(path, lofty::config::WriteOptions::default())
+        .map_err(|e| format!("failed to save {}: {e}", path.display()))?;
+
+    Ok(())
+}
+
+// Parse a single LRC time tag, e.g. "01:23.45" or "01:23.456", into milliseconds.
+fn parse_lrc_timestamp(tag: &str) -> Option<i64> {
+    let (minutes, rest) = tag.split_once(':')?;
+    let minutes: i64 = minutes.trim().parse().ok()?;
+
+    let (seconds, fraction) = match rest.split_once('.') {
+        Some((seconds, fraction)) => (seconds, Some(fraction)),
+        None => (rest, None),
+    };
+    let seconds: i64 = seconds.trim().parse().ok()?;
 
-    if let Some(tagged_file) = tagged_file_res {
-        let tag = tagged_file
-            .primary_tag()
-            .or_else(|| tagged_file.first_tag());
-        let properties = tagged_file.properties();
+    let fraction_ms: i64 = match fraction {
+        Some(fraction) => format!("{:0<3}", &fraction[..fraction.len().min(3)])
+            .parse()
+            .ok()?,
+        None => 0,
+    };
 
-        let mut cover_base64 = None;
-        if let Some(t) = tag {
-            if let Some(picture) = t.pictures().first() {
-                let b64 = BASE64_STANDARD.encode(picture.data());
-                let mime = picture
-                    .mime_type()
-                    .map(|m| m.as_str())
-                    .unwrap_or("image/jpeg");
-                cover_base64 = Some(format!("data:{};base64,{}", mime, b64));
+    Some(minutes * 60_000 + seconds * 1_000 + fraction_ms)
+}
+
+// Parse LRC-format lyrics, resolving `[mm:ss.xx]` time tags into millisecond
+// offsets and applying a global `[offset:±ms]` shift if present. ID tags like
+// `[ti:]`/`[ar:]`/`[al:]` are recognized and dropped.
+fn parse_lrc(content: &str) -> Lyrics {
+    let mut offset_ms: i64 = 0;
+    let mut entries: Vec<(i64, String)> = Vec::new();
+    let mut synced = false;
+
+    for raw_line in content.lines() {
+        let line = raw_line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let mut rest = line;
+        let mut tags = Vec::new();
+        while let Some(stripped) = rest.strip_prefix('[') {
+            match stripped.find(']') {
+                Some(end) => {
+                    tags.push(&stripped[..end]);
+                    rest = &stripped[end + 1..];
+                }
+                None => break,
+            }
+        }
+
+        if tags.is_empty() {
+            continue;
+        }
+
+        let text = rest.trim();
+        for tag in tags {
+            if let Some(shift) = tag.strip_prefix("offset:") {
+                if let Ok(ms) = shift.trim().trim_start_matches('+').parse::<i64>() {
+                    offset_ms = ms;
+                }
+                continue;
+            }
+            if matches!(
+                tag.split(':').next(),
+                Some("ti" | "ar" | "al" | "by" | "re" | "ve" | "length")
+            ) {
+                continue;
+            }
+            if let Some(ms) = parse_lrc_timestamp(tag) {
+                synced = true;
+                entries.push((ms, text.to_string()));
             }
         }
+    }
 
-        return Some(AudioMetadata {
-            title: tag.and_then(|t| t.title().map(|s| s.into_owned())),
-            artist: tag.and_then(|t| t.artist().map(|s| s.into_owned())),
-            album: tag.and_then(|t| t.album().map(|s| s.into_owned())),
-            duration: Some(properties.duration().as_secs()),
-            cover: cover_base64,
-        });
+    if synced {
+        for entry in entries.iter_mut() {
+            entry.0 += offset_ms;
+        }
+        entries.sort_by_key(|(ms, _)| *ms);
     }
 
-    None
+    Lyrics {
+        lines: entries
+            .into_iter()
+            .map(|(offset_ms, text)| LyricLine { offset_ms, text })
+            .collect(),
+        synced,
+    }
+}
+
+#[tauri::command]
+fn get_lyrics(path: String) -> Option<Lyrics> {
+    let path = Path::new(&path);
+
+    if !is_valid_audio_path(path) {
+        return None;
+    }
+
+    if let Ok(content) = std::fs::read_to_string(path.with_extension("lrc")) {
+        return Some(parse_lrc(&content));
+    }
+
+    let tagged_file = Probe::open(path).ok()?.read().ok()?;
+    let tag = tagged_file
+        .primary_tag()
+        .or_else(|| tagged_file.first_tag())?;
+    let text = tag.get_string(&ItemKey::Lyrics)?;
+
+    Some(Lyrics {
+        lines: text
+            .lines()
+            .map(|line| LyricLine {
+                offset_ms: 0,
+                text: line.to_string(),
+            })
+            .collect(),
+        synced: false,
+    })
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -82,7 +434,13 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_fs::init())
-        .invoke_handler(tauri::generate_handler![get_metadata])
+        .invoke_handler(tauri::generate_handler![
+            get_metadata,
+            get_metadata_batch,
+            set_metadata,
+            scan_library,
+            get_lyrics
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }